@@ -1,11 +1,11 @@
 //! Print all the 7-smooth numbers less than `2^64`.
 
-use smooth_numbers::smooth;
+use smooth_numbers::smooth_below;
 use std::io::{stdout, Write};
 
 fn main() {
     let mut stdout = stdout().lock();
-    for (i, n) in smooth(7, 85_348).iter().enumerate() {
+    for (i, n) in smooth_below(7, u64::MAX).iter().enumerate() {
         // ignore writing errors
         let _ = writeln!(stdout, "{:5}: {}", i + 1, n);
     }
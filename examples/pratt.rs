@@ -1,9 +1,9 @@
 //! Print all the 3-smooth numbers less than `2^64`.
 
-use smooth_numbers::pratt;
+use smooth_numbers::pratt_iter;
 
 fn main() {
-    for n in pratt(1344) {
+    for n in pratt_iter() {
         println!("{n}");
     }
 }
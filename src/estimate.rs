@@ -0,0 +1,163 @@
+/*! Fast estimate of the smooth-number counting function.
+
+[`count_smooth_below`](crate::count_smooth_below) computes the exact count
+`\Psi(limit, k)` of `k`-smooth numbers below `limit`, but it has to generate
+every one of them to do it. This module provides an asymptotic estimate of
+the same quantity via the Dickman-de Bruijn `\rho` function, the way
+[`primal-estimate`](https://docs.rs/primal-estimate) bounds `\pi(n)` without
+sieving — useful for pre-sizing allocations or choosing `n` before calling
+into the exact generators.
+*/
+
+/// Grid step used to tabulate [`dickman_rho`], chosen so that one unit of
+/// `u` is an exact, rounding-free number of grid points.
+const GRID_STEP: f64 = 1.0 / 128.0;
+const STEPS_PER_UNIT: usize = 128;
+
+fn grid_index(u: f64) -> usize {
+    (u / GRID_STEP).round() as usize
+}
+
+/// Tabulates `rho` on the grid `0, GRID_STEP, 2*GRID_STEP, ...` up to (and
+/// including) `u_max`, applying the integral equation one grid interval at a
+/// time via the trapezoidal rule.
+fn rho_table(u_max: f64) -> Vec<f64> {
+    let len = (grid_index(u_max) + 1).max(STEPS_PER_UNIT + 1);
+    let mut table = vec![1.0; len];
+
+    let mut integral = 0.0;
+    for i in (STEPS_PER_UNIT + 1)..table.len() {
+        let u_prev = (i - 1) as f64 * GRID_STEP;
+        let u_cur = i as f64 * GRID_STEP;
+        let f_prev = table[i - 1 - STEPS_PER_UNIT] / u_prev;
+        let f_cur = table[i - STEPS_PER_UNIT] / u_cur;
+        integral += GRID_STEP / 2.0 * (f_prev + f_cur);
+        // `rho` is never negative; clamp away the truncation error that the
+        // fixed-step trapezoidal rule accumulates once `rho` itself gets
+        // tiny (roughly `u > 6`), rather than let it go visibly wrong.
+        table[i] = (1.0 - integral).max(0.0);
+    }
+    table
+}
+
+/** The Dickman-de Bruijn function `\rho(u)`.
+
+`\rho(u)` is the density of `y`-smooth numbers around `x`, as a function of
+`u = ln(x) / ln(y)`: it satisfies `\rho(u) = 1` for `0 <= u <= 1`, and for
+`u > 1` the delay differential equation `u \rho'(u) = -\rho(u-1)`,
+equivalently the integral equation `\rho(u) = 1 - \int_1^u \rho(t-1)/t \, dt`.
+
+This evaluates `\rho` by tabulating it on a grid of step `1/128` from `0` up
+to `u`, using the integral equation one grid interval at a time via the
+trapezoidal rule. That is accurate for small `u` (the error is within `1e-3`
+of the true value up to around `u = 5`), but the fixed step means truncation
+error compounds as `u` grows and `\rho` itself shrinks: around `u = 6` the
+error is a sizeable fraction of `\rho` itself, and from about `u = 7` on the
+result is clamped to `0.0` rather than drifting negative.
+
+# Examples
+
+```
+# use smooth_numbers::dickman_rho;
+assert_eq!(dickman_rho(0.0), 1.0);
+assert_eq!(dickman_rho(1.0), 1.0);
+assert!((dickman_rho(2.0) - (1.0 - 2f64.ln())).abs() < 1e-3);
+assert!(dickman_rho(3.0) < dickman_rho(2.0));
+```
+*/
+pub fn dickman_rho(u: f64) -> f64 {
+    if u <= 1.0 {
+        return if u < 0.0 { 0.0 } else { 1.0 };
+    }
+    rho_table(u)[grid_index(u)]
+}
+
+/** Estimates `\Psi(limit, k)`, the number of `k`-smooth numbers `<= limit`,
+using the Dickman-de Bruijn function: `\Psi(x, y) \approx x \cdot \rho(u)`
+with `u = ln(x) / ln(y)`.
+
+This is the asymptotic estimate used for `\pi(n)`-style pre-sizing, not an
+exact count. For small limits it underestimates considerably, even when `u`
+is small: `estimate_count_smooth_below(7, 100.0)` is about `16.5`, against
+`45` smooth numbers below `100`. Since [`dickman_rho`] is clamped to `0.0`
+from about `u = 7`, the estimate is `0.0` there, which for a fixed `limit`
+means small `k` (e.g. every `k <= 7` with `limit` near `2^64`). Use
+[`count_smooth_below`](crate::count_smooth_below) when the exact count is
+needed.
+
+# Examples
+
+```
+# use smooth_numbers::estimate_count_smooth_below;
+// u = ln(limit) / ln(k) is small here, where the estimate is closest.
+let estimate = estimate_count_smooth_below(7, 100.0);
+assert!(estimate > 0.0 && estimate < 100.0);
+```
+*/
+pub fn estimate_count_smooth_below(k: u64, limit: f64) -> f64 {
+    if limit < 1.0 {
+        return 0.0;
+    }
+    if k < 2 {
+        // Only `1` itself has no prime factors.
+        return 1.0;
+    }
+    let u = limit.ln() / (k as f64).ln();
+    limit * dickman_rho(u)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dickman_rho_is_one_up_to_one() {
+        for u in [-1.0, 0.0, 0.5, 1.0] {
+            assert_eq!(dickman_rho(u), if u < 0.0 { 0.0 } else { 1.0 });
+        }
+    }
+
+    #[test]
+    fn dickman_rho_matches_known_values() {
+        // rho(2) = 1 - ln(2)
+        assert!((dickman_rho(2.0) - (1.0 - 2f64.ln())).abs() < 1e-3);
+        // rho(3) ~ 0.0486 (see e.g. OEIS A065500 / standard tables)
+        assert!((dickman_rho(3.0) - 0.0486).abs() < 1e-3);
+    }
+
+    #[test]
+    fn dickman_rho_is_decreasing() {
+        let mut prev = dickman_rho(0.0);
+        let mut u = 0.25;
+        while u <= 10.0 {
+            let rho = dickman_rho(u);
+            assert!(rho <= prev);
+            prev = rho;
+            u += 0.25;
+        }
+    }
+
+    #[test]
+    fn estimate_count_smooth_below_edge_cases() {
+        assert_eq!(estimate_count_smooth_below(3, 0.0), 0.0);
+        assert_eq!(estimate_count_smooth_below(3, 0.5), 0.0);
+        assert_eq!(estimate_count_smooth_below(0, 10.0), 1.0);
+        assert_eq!(estimate_count_smooth_below(1, 10.0), 1.0);
+    }
+
+    #[test]
+    fn estimate_count_smooth_below_is_the_right_order_of_magnitude() {
+        use crate::count_smooth_below;
+
+        // `u` stays small here, the regime where the Dickman approximation
+        // is meaningful rather than a last-resort order-of-magnitude guess.
+        for (k, limit) in [(7, 100u64), (11, 1_000), (13, 10_000)] {
+            let exact = count_smooth_below(k, limit) as f64;
+            let estimate = estimate_count_smooth_below(k, limit as f64);
+            assert!(
+                estimate > 0.0 && estimate < 10.0 * exact.max(1.0),
+                "k={k} limit={limit} exact={exact} estimate={estimate}"
+            );
+        }
+    }
+}
@@ -0,0 +1,429 @@
+/*! Factorization-based smoothness tests.
+
+This module answers the question the generators in the crate root don't:
+given a particular `u64`, is it `k`-smooth? It implements a small
+self-contained factorizer rather than pulling in a general-purpose
+factorization crate:
+
+- small prime factors are stripped by trial division against a sieve of
+  the primes below [`TRIAL_DIVISION_LIMIT`];
+- the remaining cofactor is tested for primality with deterministic
+  Miller-Rabin (the witness set `{2,3,5,7,11,13,17,19,23,29,31,37}` is
+  provably sufficient for every `u64`);
+- composite cofactors are split with Pollard's rho, using Floyd's
+  tortoise-and-hare cycle detection with the gcd taken once per batch of
+  steps (not Brent's power-of-two cycle detection).
+
+Both the Miller-Rabin exponentiation and the rho iteration do their
+modular multiplications in Montgomery form, since the cofactors here are
+typically large enough that it pays for itself.
+*/
+
+/// Upper bound (inclusive) of the primes used for the initial trial division pass.
+const TRIAL_DIVISION_LIMIT: u64 = 300;
+
+/// Deterministic Miller-Rabin witnesses, sufficient for all `n < 2^64`.
+const MILLER_RABIN_WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Modular arithmetic in Montgomery form for a fixed odd modulus `n`.
+#[derive(Clone, Copy)]
+struct Montgomery {
+    n: u64,
+    /// `-n^-1 mod 2^64`.
+    n_inv: u64,
+    /// `R^2 mod n`, where `R = 2^64`, used to move values into Montgomery form.
+    r2: u64,
+}
+
+impl Montgomery {
+    fn new(n: u64) -> Self {
+        debug_assert!(n % 2 == 1, "Montgomery arithmetic requires an odd modulus");
+
+        // Newton's iteration for the inverse of `n` mod `2^64`: each step doubles
+        // the number of correct bits, and `inv = n` is already correct mod `2^3`
+        // for odd `n` (`n * n == 1 mod 8`), so five iterations give 96 > 64 bits.
+        let mut inv = n;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n.wrapping_mul(inv)));
+        }
+        let n_inv = inv.wrapping_neg();
+
+        let r_mod_n = ((1u128 << 64) % n as u128) as u64;
+        let r2 = ((r_mod_n as u128 * r_mod_n as u128) % n as u128) as u64;
+
+        Montgomery { n, n_inv, r2 }
+    }
+
+    /// REDC: given `t < n * 2^64`, returns `t * R^-1 mod n`.
+    fn redc(self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.n_inv);
+        let mn = m as u128 * self.n as u128;
+        let (sum, overflow) = t.overflowing_add(mn);
+        let mut u = sum >> 64;
+        if overflow {
+            u += 1u128 << 64;
+        }
+        let n = self.n as u128;
+        if u >= n {
+            u -= n;
+        }
+        u as u64
+    }
+
+    fn to_mont(self, a: u64) -> u64 {
+        self.redc(a as u128 * self.r2 as u128)
+    }
+
+    fn mul(self, a: u64, b: u64) -> u64 {
+        self.redc(a as u128 * b as u128)
+    }
+
+    fn add(self, a: u64, b: u64) -> u64 {
+        let s = a as u128 + b as u128;
+        let n = self.n as u128;
+        (if s >= n { s - n } else { s }) as u64
+    }
+
+    /// Absolute difference of two residues already reduced mod `n`.
+    fn abs_diff(self, a: u64, b: u64) -> u64 {
+        a.abs_diff(b)
+    }
+}
+
+fn pow_mod(mont: &Montgomery, base: u64, mut exp: u64) -> u64 {
+    let mut result = mont.to_mont(1);
+    let mut base = mont.to_mont(base % mont.n);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mont.mul(result, base);
+        }
+        base = mont.mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Deterministic Miller-Rabin primality test, exact for every `u64`.
+fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &MILLER_RABIN_WITNESSES {
+        if n == p {
+            return true;
+        }
+        if n.is_multiple_of(p) {
+            return false;
+        }
+    }
+
+    let mut d = n - 1;
+    let mut r = 0u32;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        r += 1;
+    }
+
+    let mont = Montgomery::new(n);
+    let one = mont.to_mont(1);
+    let minus_one = mont.to_mont(n - 1);
+
+    'witness: for &a in &MILLER_RABIN_WITNESSES {
+        let mut x = pow_mod(&mont, a, d);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 0..r - 1 {
+            x = mont.mul(x, x);
+            if x == minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let x = x.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Finds a nontrivial factor of the composite, odd `n` using Pollard's rho
+/// with Floyd's cycle detection (`y` takes two steps for each step of `x`),
+/// multiplying the differences `|x - y|` together and taking one gcd per
+/// batch of `BATCH` steps. Brent's power-of-two cycle detection is not used.
+fn pollard_rho(n: u64) -> u64 {
+    const BATCH: u32 = 128;
+
+    let mont = Montgomery::new(n);
+    let mut seed = n ^ 0x9E3779B97F4A7C15;
+
+    'restart: loop {
+        seed = splitmix64(seed);
+        let c = mont.to_mont(1 + seed % (n - 1));
+        seed = splitmix64(seed);
+        let start = mont.to_mont(seed % n);
+
+        let mut x = start;
+        let mut y = start;
+
+        loop {
+            // Checkpoint the state at the start of the batch so a degenerate
+            // batched gcd (see below) can be re-walked one step at a time.
+            let (checkpoint_x, checkpoint_y) = (x, y);
+            let mut q = mont.to_mont(1);
+
+            for _ in 0..BATCH {
+                x = mont.add(mont.mul(x, x), c);
+                y = mont.add(mont.mul(y, y), c);
+                y = mont.add(mont.mul(y, y), c);
+                q = mont.mul(q, mont.abs_diff(x, y));
+            }
+
+            let d = gcd(q, n);
+            if d == 1 {
+                continue;
+            }
+            if d < n {
+                return d;
+            }
+
+            // `d == n`: the batched product vanished mod `n` without pinning
+            // down a single splitting step. This happens when the
+            // differences in one batch pick up every prime power of `n`:
+            // e.g. when two distinct prime factors both close their cycles
+            // within the batch, or when `n` has a repeated prime factor `p`
+            // whose cycle length mod `p` is shorter than `BATCH`, so the
+            // trajectory wraps it more than once within the batch and the
+            // accumulated differences pick up two or more factors of `p`.
+            // Re-walk the same batch from the
+            // checkpoint one step at a time, taking the gcd against each
+            // individual difference, to pin down exactly where it splits.
+            let mut x = checkpoint_x;
+            let mut y = checkpoint_y;
+            for _ in 0..BATCH {
+                x = mont.add(mont.mul(x, x), c);
+                y = mont.add(mont.mul(y, y), c);
+                y = mont.add(mont.mul(y, y), c);
+                let d = gcd(mont.abs_diff(x, y), n);
+                if d > 1 && d < n {
+                    return d;
+                }
+                if d == n {
+                    break;
+                }
+            }
+            // Either every step in this batch was a trivial (d == 1) or a
+            // total collision (d == n in a single step): this `c` can't
+            // split `n` this way, so try a different one.
+            continue 'restart;
+        }
+    }
+}
+
+/// Full prime factorization of `n` as `(prime, exponent)` pairs, in no
+/// particular order. Returns an empty vector for `n < 2`.
+fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    if n < 2 {
+        return factors;
+    }
+
+    let sieve = primal::Sieve::new(TRIAL_DIVISION_LIMIT as usize);
+    for p in sieve
+        .primes_from(2)
+        .take_while(|&p| p as u64 <= TRIAL_DIVISION_LIMIT)
+        .map(|p| p as u64)
+    {
+        if p * p > n {
+            break;
+        }
+        if n.is_multiple_of(p) {
+            let mut exp = 0;
+            while n.is_multiple_of(p) {
+                n /= p;
+                exp += 1;
+            }
+            factors.push((p, exp));
+        }
+    }
+
+    if n > 1 {
+        factor_large(n, &mut factors);
+    }
+    factors
+}
+
+/// Factors a cofactor left over by trial division (either a prime, or a
+/// number with no prime factor below [`TRIAL_DIVISION_LIMIT`]), merging into
+/// `factors` (which may already contain some of its primes).
+fn factor_large(n: u64, factors: &mut Vec<(u64, u32)>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        match factors.iter_mut().find(|(p, _)| *p == n) {
+            Some((_, exp)) => *exp += 1,
+            None => factors.push((n, 1)),
+        }
+        return;
+    }
+    let d = pollard_rho(n);
+    factor_large(d, factors);
+    factor_large(n / d, factors);
+}
+
+/** Returns the largest prime factor of `n`.
+
+`1` has no prime factors, so by convention `largest_prime_factor(1) == 1`.
+
+# Examples
+
+```
+# use smooth_numbers::largest_prime_factor;
+assert_eq!(largest_prime_factor(1), 1);
+assert_eq!(largest_prime_factor(13195), 29);
+assert_eq!(largest_prime_factor(600851475143), 6857);
+```
+*/
+pub fn largest_prime_factor(n: u64) -> u64 {
+    factorize(n)
+        .into_iter()
+        .map(|(p, _)| p)
+        .max()
+        .unwrap_or(1)
+}
+
+/** Returns the largest divisor of `n` whose prime factors are all `<= k`.
+
+`1` has no prime factors, so `smooth_part(1, k) == 1` for every `k`,
+and `smooth_part(0, k) == 0` since `0` has no divisors to speak of.
+
+# Examples
+
+```
+# use smooth_numbers::smooth_part;
+assert_eq!(smooth_part(1, 0), 1);
+assert_eq!(smooth_part(720, 3), 144); // 720 = 2^4 * 3^2 * 5
+assert_eq!(smooth_part(720, 5), 720);
+assert_eq!(smooth_part(17, 13), 1);
+```
+*/
+pub fn smooth_part(n: u64, k: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    factorize(n)
+        .into_iter()
+        .filter(|&(p, _)| p <= k)
+        .map(|(p, e)| p.pow(e))
+        .product()
+}
+
+/** Returns whether `n` is `k`-smooth, i.e. whether every prime factor of `n`
+is `<= k`. This is the natural inverse of the generators in the crate root:
+they list the `k`-smooth numbers in order, this checks whether a specific
+`u64` belongs to that list.
+
+`1` has no prime factors, so it is vacuously `k`-smooth for every `k`,
+matching the convention used by [`smooth`](crate::smooth).
+
+# Examples
+
+```
+# use smooth_numbers::is_smooth;
+assert!(is_smooth(1, 0));
+assert!(is_smooth(1024, 2));
+assert!(is_smooth(720, 5));
+assert!(!is_smooth(720, 3));
+assert!(!is_smooth(17, 13));
+```
+*/
+pub fn is_smooth(n: u64, k: u64) -> bool {
+    if n <= 1 {
+        return true;
+    }
+    largest_prime_factor(n) <= k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_prime_factor_known_values() {
+        assert_eq!(largest_prime_factor(1), 1);
+        assert_eq!(largest_prime_factor(2), 2);
+        assert_eq!(largest_prime_factor(13195), 29);
+        assert_eq!(largest_prime_factor(600851475143), 6857);
+        assert_eq!(largest_prime_factor(1_000_000_007), 1_000_000_007);
+    }
+
+    #[test]
+    fn largest_prime_factor_on_products_of_large_primes() {
+        // Two large primes close to 2^32, well past the trial-division limit.
+        let p = 4_294_967_291;
+        let q = 4_294_967_279;
+        assert_eq!(largest_prime_factor(p * q), p);
+    }
+
+    #[test]
+    fn largest_prime_factor_on_square_of_prime_above_trial_division() {
+        // A repeated prime factor just above `TRIAL_DIVISION_LIMIT`: its
+        // cycle mod `p` in Pollard's rho is much shorter than the gcd batch,
+        // so a naive batched-gcd fallback can spin forever on these.
+        for p in [307, 311, 313] {
+            assert_eq!(largest_prime_factor(p * p), p);
+        }
+    }
+
+    #[test]
+    fn smooth_part_known_values() {
+        assert_eq!(smooth_part(0, 10), 0);
+        assert_eq!(smooth_part(1, 0), 1);
+        assert_eq!(smooth_part(720, 3), 144);
+        assert_eq!(smooth_part(720, 5), 720);
+        assert_eq!(smooth_part(17, 13), 1);
+    }
+
+    #[test]
+    fn is_smooth_matches_smooth_part() {
+        for n in 1..2000u64 {
+            for k in [0, 1, 2, 3, 5, 7, 11] {
+                assert_eq!(is_smooth(n, k), smooth_part(n, k) == n);
+            }
+        }
+    }
+
+    #[test]
+    fn is_prime_matches_trial_division() {
+        fn trial_division_is_prime(n: u64) -> bool {
+            if n < 2 {
+                return false;
+            }
+            let mut d = 2;
+            while d * d <= n {
+                if n.is_multiple_of(d) {
+                    return false;
+                }
+                d += 1;
+            }
+            true
+        }
+
+        for n in 0..10_000u64 {
+            assert_eq!(is_prime(n), trial_division_is_prime(n), "n = {n}");
+        }
+    }
+}
@@ -0,0 +1,249 @@
+/*! Generic integer backing for the generators in the crate root.
+
+`pratt`, `smooth` and `with_primes` only need a handful of operations from
+their output type: a multiplicative identity, conversion from a prime
+factor, multiplication, and a total order to merge candidate values. The
+[`SmoothInt`] trait captures exactly that, so the same merge logic can run
+over `u64` (what [`pratt`](crate::pratt) and friends use), `u128`, or
+(behind the `num-bigint` feature) `num_bigint::BigUint` for sequences that
+outgrow fixed-width integers.
+*/
+
+use std::ops::Mul;
+
+/// The minimal set of operations the smooth-number generators need from
+/// their output type.
+pub trait SmoothInt: Clone + Ord + Mul<Output = Self> {
+    /// The multiplicative identity, i.e. the first smooth number.
+    fn one() -> Self;
+    /// Converts a prime factor into `Self`.
+    fn from_prime(p: u64) -> Self;
+}
+
+impl SmoothInt for u64 {
+    fn one() -> Self {
+        1
+    }
+    fn from_prime(p: u64) -> Self {
+        p
+    }
+}
+
+impl SmoothInt for u128 {
+    fn one() -> Self {
+        1
+    }
+    fn from_prime(p: u64) -> Self {
+        p as u128
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl SmoothInt for num_bigint::BigUint {
+    fn one() -> Self {
+        num_bigint::BigUint::from(1u64)
+    }
+    fn from_prime(p: u64) -> Self {
+        num_bigint::BigUint::from(p)
+    }
+}
+
+/** Generic counterpart of [`pratt`](crate::pratt): generates the first `n`
+numbers of Pratt's sequence in any [`SmoothInt`] type, so the sequence can be
+continued past `u64`'s overflow point by instantiating at `u128` or, with the
+`num-bigint` feature, `num_bigint::BigUint`.
+
+# Examples
+
+```
+use smooth_numbers::pratt_generic;
+assert_eq!(pratt_generic::<u64>(10), [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
+// 1345 overflows a u64, but not a u128.
+assert_eq!(pratt_generic::<u128>(1345).len(), 1345);
+```
+*/
+pub fn pratt_generic<T: SmoothInt>(n: usize) -> Vec<T> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let two = T::from_prime(2);
+    let three = T::from_prime(3);
+
+    let mut v = Vec::with_capacity(n);
+    v.push(T::one());
+
+    let mut i_two = 0;
+    let mut i_three = 0;
+
+    for _ in 1..n {
+        let times_two = two.clone() * v[i_two].clone();
+        let times_three = three.clone() * v[i_three].clone();
+        match times_two.cmp(&times_three) {
+            std::cmp::Ordering::Less => {
+                v.push(times_two);
+                i_two += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                v.push(times_two);
+                i_two += 1;
+                i_three += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                v.push(times_three);
+                i_three += 1;
+            }
+        }
+    }
+    v
+}
+
+/** Generic counterpart of [`smooth`](crate::smooth): generates the first `n`
+`k`-smooth numbers in any [`SmoothInt`] type.
+
+# Examples
+
+```
+use smooth_numbers::smooth_generic;
+assert_eq!(smooth_generic::<u64>(3, 10), [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
+assert_eq!(smooth_generic::<u128>(3, 1345).len(), 1345);
+```
+*/
+pub fn smooth_generic<T: SmoothInt>(k: usize, n: usize) -> Vec<T> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    if k < 2 {
+        return vec![T::one()];
+    }
+
+    if k == 2 {
+        let two = T::from_prime(2);
+        let mut v = Vec::with_capacity(n);
+        v.push(T::one());
+        let mut x = T::one();
+        for _ in 1..n {
+            x = two.clone() * x;
+            v.push(x.clone());
+        }
+        return v;
+    }
+
+    let sieve = primal::Sieve::new(k);
+    let primes: Vec<u64> = sieve
+        .primes_from(2)
+        .take_while(|&p| p <= k)
+        .map(|p| p as u64)
+        .collect();
+    with_primes_generic(&primes, n)
+}
+
+/** Generic counterpart of [`with_primes`](crate::with_primes): generates the
+first `n` smooth numbers whose prime factors are among `primes`, in any
+[`SmoothInt`] type.
+
+# Examples
+
+```
+use smooth_numbers::with_primes_generic;
+assert_eq!(
+    with_primes_generic::<u64>(&[2, 3], 10),
+    [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]
+);
+assert_eq!(with_primes_generic::<u128>(&[2, 3], 1345).len(), 1345);
+```
+*/
+pub fn with_primes_generic<T: SmoothInt>(primes: &[u64], n: usize) -> Vec<T> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let num_primes = primes.len();
+
+    if num_primes == 0 {
+        return vec![T::one()];
+    }
+
+    if num_primes == 1 {
+        let p = T::from_prime(primes[0]);
+        let mut v = Vec::with_capacity(n);
+        v.push(T::one());
+        let mut x = T::one();
+        for _ in 1..n {
+            x = p.clone() * x;
+            v.push(x.clone());
+        }
+        return v;
+    }
+
+    let primes: Vec<T> = primes.iter().map(|&p| T::from_prime(p)).collect();
+    let mut indices: Vec<usize> = vec![0; num_primes];
+    let mut v = Vec::with_capacity(n);
+    v.push(T::one());
+
+    for _ in 1..n {
+        let i = (0..num_primes)
+            .min_by_key(|&j| primes[j].clone() * v[indices[j]].clone())
+            .expect("cannot find next index");
+        let new = primes[i].clone() * v[indices[i]].clone();
+        v.push(new.clone());
+        for j in 0..num_primes {
+            if primes[j].clone() * v[indices[j]].clone() == new {
+                indices[j] += 1;
+            }
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pratt_generic_matches_u64() {
+        for n in [0, 1, 10, 100, 1343] {
+            assert_eq!(
+                pratt_generic::<u128>(n),
+                pratt_generic::<u64>(n)
+                    .into_iter()
+                    .map(u128::from)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn pratt_generic_survives_u64_overflow_point() {
+        // crate::pratt(1345) panics with "attempt to multiply with overflow";
+        // the u128 backing keeps going past it.
+        assert_eq!(pratt_generic::<u128>(1345).len(), 1345);
+    }
+
+    #[test]
+    fn smooth_generic_matches_u64() {
+        for (k, n) in [(2, 64), (3, 100), (5, 100)] {
+            assert_eq!(
+                smooth_generic::<u128>(k, n),
+                smooth_generic::<u64>(k, n)
+                    .into_iter()
+                    .map(u128::from)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn with_primes_generic_matches_u64() {
+        for (primes, n) in [(&[2][..], 64), (&[2, 3], 100), (&[2, 3, 5], 100)] {
+            assert_eq!(
+                with_primes_generic::<u128>(primes, n),
+                with_primes_generic::<u64>(primes, n)
+                    .into_iter()
+                    .map(u128::from)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -0,0 +1,191 @@
+/*! Lazy, incremental generation of smooth numbers.
+
+`pratt`, `smooth` and `with_primes` eagerly allocate a `Vec` of a fixed
+length `n`, so callers have to commit to a count up front and pay for
+storage proportional to it even if they only want the first few values or
+everything up to some magnitude. [`SmoothIter`] runs the same merge walk
+incrementally instead, so it composes with `take`, `take_while` and
+`enumerate` and can stream its output.
+*/
+
+/** An iterator that lazily yields smooth numbers one at a time.
+
+Constructed with [`smooth_iter`], [`pratt_iter`] or [`with_primes_iter`].
+The merge algorithm needs a back-history of already-emitted values to find
+the next per-prime candidate, so the iterator still retains every value it
+has produced - memory use grows with the number of elements consumed, even
+though callers no longer have to decide that number up front.
+
+Uses checked multiplication internally, so `next` returns `None` once the
+next value would overflow a `u64`, instead of panicking the way
+`pratt`/`smooth`/`with_primes` do.
+*/
+pub struct SmoothIter {
+    primes: Vec<u64>,
+    indices: Vec<usize>,
+    history: Vec<u64>,
+    exhausted: bool,
+}
+
+impl SmoothIter {
+    fn new(primes: Vec<u64>) -> Self {
+        let indices = vec![0; primes.len()];
+        SmoothIter {
+            primes,
+            indices,
+            history: Vec::new(),
+            exhausted: false,
+        }
+    }
+}
+
+impl Iterator for SmoothIter {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.exhausted {
+            return None;
+        }
+
+        if self.history.is_empty() {
+            self.history.push(1);
+            return Some(1);
+        }
+
+        if self.primes.is_empty() {
+            // No prime factors allowed at all: `1` was the only smooth number.
+            self.exhausted = true;
+            return None;
+        }
+
+        let num_primes = self.primes.len();
+        let candidates: Vec<Option<u64>> = (0..num_primes)
+            .map(|j| self.primes[j].checked_mul(self.history[self.indices[j]]))
+            .collect();
+
+        let i = (0..num_primes)
+            .min_by_key(|&j| candidates[j].unwrap_or(u64::MAX))
+            .expect("num_primes > 0");
+
+        let Some(new) = candidates[i] else {
+            self.exhausted = true;
+            return None;
+        };
+
+        self.history.push(new);
+        for (j, &candidate) in candidates.iter().enumerate() {
+            if candidate == Some(new) {
+                self.indices[j] += 1;
+            }
+        }
+        Some(new)
+    }
+}
+
+/** Iterator version of [`pratt`](crate::pratt): lazily yields the numbers of
+Pratt's sequence, one at a time, until the next one would overflow a `u64`.
+
+# Examples
+
+```
+use smooth_numbers::pratt_iter;
+assert_eq!(
+    pratt_iter().take(10).collect::<Vec<_>>(),
+    [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]
+);
+assert_eq!(pratt_iter().count(), 1344);
+```
+*/
+pub fn pratt_iter() -> SmoothIter {
+    with_primes_iter(&[2, 3])
+}
+
+/** Iterator version of [`smooth`](crate::smooth): lazily yields the
+`k`-smooth numbers, one at a time, until the next one would overflow a `u64`.
+
+# Examples
+
+```
+use smooth_numbers::smooth_iter;
+assert_eq!(
+    smooth_iter(3).take_while(|&n| n < 20).collect::<Vec<_>>(),
+    [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]
+);
+```
+*/
+pub fn smooth_iter(k: usize) -> SmoothIter {
+    if k < 2 {
+        return SmoothIter::new(Vec::new());
+    }
+
+    let sieve = primal::Sieve::new(k);
+    let primes: Vec<u64> = sieve
+        .primes_from(2)
+        .take_while(|&p| p <= k)
+        .map(|p| p as u64)
+        .collect();
+    SmoothIter::new(primes)
+}
+
+/** Iterator version of [`with_primes`](crate::with_primes): lazily yields
+the smooth numbers whose prime factors are among `primes`, one at a time,
+until the next one would overflow a `u64`.
+
+# Examples
+
+```
+use smooth_numbers::with_primes_iter;
+assert_eq!(
+    with_primes_iter(&[2, 3]).take(10).collect::<Vec<_>>(),
+    [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]
+);
+```
+*/
+pub fn with_primes_iter(primes: &[u64]) -> SmoothIter {
+    SmoothIter::new(primes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pratt_iter_matches_pratt() {
+        assert_eq!(
+            pratt_iter().take(100).collect::<Vec<_>>(),
+            crate::pratt(100)
+        );
+    }
+
+    #[test]
+    fn pratt_iter_terminates_instead_of_overflowing() {
+        let v: Vec<u64> = pratt_iter().collect();
+        assert_eq!(v.len(), 1344);
+        assert_eq!(v.last(), Some(&17991041643939889152));
+    }
+
+    #[test]
+    fn smooth_iter_matches_smooth() {
+        for (k, n) in [(0, 1), (1, 1), (2, 64), (3, 100), (5, 100)] {
+            assert_eq!(
+                smooth_iter(k).take(n).collect::<Vec<_>>(),
+                crate::smooth(k, n)
+            );
+        }
+    }
+
+    #[test]
+    fn with_primes_iter_matches_with_primes() {
+        for primes in [&[][..], &[2], &[2, 3], &[2, 3, 5]] {
+            assert_eq!(
+                with_primes_iter(primes).take(50).collect::<Vec<_>>(),
+                crate::with_primes(primes, 50)
+            );
+        }
+    }
+
+    #[test]
+    fn with_primes_iter_empty_yields_only_one() {
+        assert_eq!(with_primes_iter(&[]).collect::<Vec<_>>(), [1]);
+    }
+}
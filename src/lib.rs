@@ -17,6 +17,16 @@ assert_eq!(smooth(5, 10), [1, 2, 3, 4, 5, 6, 8, 9, 10, 12]);
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+mod estimate;
+mod factor;
+mod int;
+mod iter;
+
+pub use estimate::{dickman_rho, estimate_count_smooth_below};
+pub use factor::{is_smooth, largest_prime_factor, smooth_part};
+pub use int::{pratt_generic, smooth_generic, with_primes_generic, SmoothInt};
+pub use iter::{pratt_iter, smooth_iter, with_primes_iter, SmoothIter};
+
 /** Generates the first `n` numbers in the Pratt's sequence.
 
 Pratt's sequence is the sequence of numbers of the form `2^i * 3^j`,
@@ -31,36 +41,7 @@ They provide the best-known sequence of gaps for [Shellsort] (best worst-case ti
 [Shellsort]: https://en.wikipedia.org/wiki/Shellsort#Gap_sequences
 */
 pub fn pratt(n: usize) -> Vec<u64> {
-    if n == 0 {
-        return Vec::new();
-    }
-
-    let mut v = Vec::with_capacity(n);
-    v.push(1);
-
-    let mut two = 0;
-    let mut three = 0;
-
-    for _ in 1..n {
-        let times_two = 2 * v[two];
-        let times_three = 3 * v[three];
-        match (times_two).cmp(&times_three) {
-            std::cmp::Ordering::Less => {
-                v.push(times_two);
-                two += 1;
-            }
-            std::cmp::Ordering::Equal => {
-                v.push(times_two);
-                two += 1;
-                three += 1;
-            }
-            std::cmp::Ordering::Greater => {
-                v.push(times_three);
-                three += 1;
-            }
-        }
-    }
-    v
+    pratt_generic(n)
 }
 
 /** Generates the first `n` `k`-smooth numbers, i.e. numbers whose prime factors
@@ -102,49 +83,7 @@ assert_eq!(smooth(3, 10), [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
 ```
 */
 pub fn smooth(k: usize, n: usize) -> Vec<u64> {
-    if n == 0 {
-        return Vec::new();
-    }
-
-    if k < 2 {
-        return vec![1];
-    }
-
-    if k == 2 {
-        let mut v = Vec::with_capacity(n);
-        v.push(1);
-        let mut x = 1;
-        for _ in 1..n {
-            x *= 2;
-            v.push(x);
-        }
-        return v;
-    }
-
-    let sieve = primal::Sieve::new(k);
-    let primes: Vec<u64> = sieve
-        .primes_from(2)
-        .take_while(|&p| p <= k)
-        .map(|p| p as u64)
-        .collect();
-    let num_primes = primes.len();
-    let mut indices: Vec<usize> = vec![0; num_primes];
-    let mut v = Vec::with_capacity(n);
-    v.push(1);
-
-    for _ in 1..n {
-        let i = (0..num_primes)
-            .min_by_key(|&j| primes[j] * v[indices[j]])
-            .expect("cannot find next index");
-        let new = primes[i] * v[indices[i]];
-        v.push(new);
-        for j in 0..num_primes {
-            if primes[j] * v[indices[j]] == new {
-                indices[j] += 1;
-            }
-        }
-    }
-    v
+    smooth_generic(k, n)
 }
 
 /** Generates the first `n` smooth numbers whose prime factors are among `primes`.
@@ -180,44 +119,224 @@ assert_eq!(with_primes(&[2, 3], 10), [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
 ```
 */
 pub fn with_primes(primes: &[u64], n: usize) -> Vec<u64> {
-    if n == 0 {
+    with_primes_generic(primes, n)
+}
+
+/** Generates every `k`-smooth number strictly below `limit`, in increasing order.
+
+This is the same merge walk as [`smooth`], but bounded by value instead of by
+count, so it never needs to guess how many smooth numbers fit below a given
+magnitude (and, bounding by value rather than by count, it cannot overflow).
+
+# Examples
+
+```
+# use smooth_numbers::smooth_below;
+assert_eq!(smooth_below(3, 1), [] as [u64; 0]);
+assert_eq!(smooth_below(3, 2), [1]);
+assert_eq!(smooth_below(3, 20), [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
+```
+*/
+pub fn smooth_below(k: u64, limit: u64) -> Vec<u64> {
+    if limit <= 1 {
         return Vec::new();
     }
 
+    if k < 2 {
+        return vec![1];
+    }
+
+    let primes = primes_up_to(k);
+    merge_below(&primes, limit)
+}
+
+/** Counts the `k`-smooth numbers strictly below `limit`, i.e. `\Psi(limit, k)`.
+
+This counts `\Psi(limit, k)` directly via the standard smooth-number
+counting recurrence, rather than generating [`smooth_below`]'s list and
+measuring it, so it keeps no list in memory. Its running time still grows
+with `\Psi(limit, k)`, since the recurrence adds the count up one by one.
+
+# Examples
+
+```
+# use smooth_numbers::count_smooth_below;
+assert_eq!(count_smooth_below(3, 20), 10);
+```
+*/
+pub fn count_smooth_below(k: u64, limit: u64) -> usize {
+    if limit <= 1 {
+        return 0;
+    }
+
+    if k < 2 {
+        return 1;
+    }
+
+    let primes = primes_up_to(k);
+    // `count_smooth_le` counts integers `<= x`; shift to count `< limit`.
+    count_smooth_le(&primes, primes.len() - 1, (limit - 1) as u128)
+}
+
+/// Sieves the primes `<= k`, shared by [`smooth_below`] and [`count_smooth_below`].
+fn primes_up_to(k: u64) -> Vec<u64> {
+    let sieve = primal::Sieve::new(k as usize);
+    sieve
+        .primes_from(2)
+        .take_while(|&p| p as u64 <= k)
+        .map(|p| p as u64)
+        .collect()
+}
+
+/// Shared merge walk behind [`smooth_below`], bounded by value via `u128`
+/// arithmetic so the running products can never overflow.
+fn merge_below(primes: &[u64], limit: u64) -> Vec<u64> {
     let num_primes = primes.len();
 
     if num_primes == 0 {
         return vec![1];
     }
 
+    let limit = limit as u128;
+    let mut indices: Vec<usize> = vec![0; num_primes];
+    let mut v: Vec<u128> = vec![1];
+
+    loop {
+        let i = (0..num_primes)
+            .min_by_key(|&j| primes[j] as u128 * v[indices[j]])
+            .expect("cannot find next index");
+        let new = primes[i] as u128 * v[indices[i]];
+        if new >= limit {
+            break;
+        }
+        v.push(new);
+        for j in 0..num_primes {
+            if primes[j] as u128 * v[indices[j]] == new {
+                indices[j] += 1;
+            }
+        }
+    }
+
+    v.into_iter().map(|x| x as u64).collect()
+}
+
+/// Counts the integers `<= x` whose only prime factors are among
+/// `primes[..=i]`, via the standard smooth-number counting recurrence:
+/// `\Psi(x, p_i) = sum_{j: p_i^j <= x} \Psi(x / p_i^j, p_{i-1})`, bottoming
+/// out at `\Psi(x, p_0) = floor(log_{p_0} x) + 1`. This walks a tree of
+/// divisions rather than the merge sequence itself, so it stores nothing but
+/// the recursion; the base case steps through the powers of `p_0` one at a
+/// time, so the number of steps still grows with the count it returns.
+fn count_smooth_le(primes: &[u64], i: usize, x: u128) -> usize {
+    let p = primes[i] as u128;
+    let mut count = 0;
+    let mut power = 1u128;
+    while power <= x {
+        count += if i == 0 {
+            1
+        } else {
+            count_smooth_le(primes, i - 1, x / power)
+        };
+        if power > x / p {
+            break;
+        }
+        power *= p;
+    }
+    count
+}
+
+/** Generates the first `n` `k`-smooth numbers together with their exponent
+vector over the prime basis `2, 3, 5, ...` up to `k`, so `720` comes back as
+`(720, [4, 2, 1])` (i.e. `2^4 * 3^2 * 5^1`).
+
+This is the factored counterpart of [`smooth`]: the merge algorithm already
+knows, for each value it produces, which basis prime it was multiplied by
+and which earlier value it was multiplied onto, so it can build up the
+exponent vector incrementally instead of the caller having to re-factor
+the result.
+
+# Examples
+
+```
+# use smooth_numbers::smooth_factored;
+let factored = smooth_factored(5, 100);
+assert_eq!(factored[0], (1, vec![0, 0, 0]));
+let (value, exponents) = factored.iter().find(|&&(v, _)| v == 720).unwrap();
+assert_eq!((*value, exponents.clone()), (720, vec![4, 2, 1]));
+```
+*/
+pub fn smooth_factored(k: usize, n: usize) -> Vec<(u64, Vec<u32>)> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    if k < 2 {
+        return vec![(1, Vec::new())];
+    }
+
+    let sieve = primal::Sieve::new(k);
+    let primes: Vec<u64> = sieve
+        .primes_from(2)
+        .take_while(|&p| p <= k)
+        .map(|p| p as u64)
+        .collect();
+    with_primes_factored(&primes, n)
+}
+
+/** Generates the first `n` smooth numbers whose prime factors are among
+`primes`, together with their exponent vector over `primes`.
+
+This is the factored counterpart of [`with_primes`]; see [`smooth_factored`]
+for the rationale and an example.
+*/
+pub fn with_primes_factored(primes: &[u64], n: usize) -> Vec<(u64, Vec<u32>)> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let num_primes = primes.len();
+
+    if num_primes == 0 {
+        return vec![(1, Vec::new())];
+    }
+
     if num_primes == 1 {
         let mut v = Vec::with_capacity(n);
-        v.push(1);
+        v.push((1, vec![0]));
         let mut x = 1;
-        for _ in 1..n {
+        for exp in 1..n as u32 {
             x *= primes[0];
-            v.push(x);
+            v.push((x, vec![exp]));
         }
         return v;
     }
 
     let mut indices: Vec<usize> = vec![0; num_primes];
-    let mut v = Vec::with_capacity(n);
-    v.push(1);
+    let mut values: Vec<u64> = Vec::with_capacity(n);
+    let mut exponents: Vec<Vec<u32>> = Vec::with_capacity(n);
+    values.push(1);
+    exponents.push(vec![0; num_primes]);
 
     for _ in 1..n {
         let i = (0..num_primes)
-            .min_by_key(|&j| primes[j] * v[indices[j]])
+            .min_by_key(|&j| primes[j] * values[indices[j]])
             .expect("cannot find next index");
-        let new = primes[i] * v[indices[i]];
-        v.push(new);
+        let new = primes[i] * values[indices[i]];
+
+        let mut new_exponents = exponents[indices[i]].clone();
+        new_exponents[i] += 1;
+
         for j in 0..num_primes {
-            if primes[j] * v[indices[j]] == new {
+            if primes[j] * values[indices[j]] == new {
                 indices[j] += 1;
             }
         }
+
+        values.push(new);
+        exponents.push(new_exponents);
     }
-    v
+
+    values.into_iter().zip(exponents).collect()
 }
 
 #[cfg(test)]
@@ -325,4 +444,85 @@ mod tests {
     fn with_primes_2_first_to_overflow() {
         let _ = with_primes(&[2, 3], 1345).last();
     }
+
+    #[test]
+    fn smooth_below_matches_smooth() {
+        for k in [0, 1, 2, 3, 5, 7] {
+            let below = smooth_below(k, 100);
+            let expected: Vec<u64> = smooth(k as usize, 64)
+                .into_iter()
+                .take_while(|&n| n < 100)
+                .collect();
+            assert_eq!(below, expected);
+        }
+    }
+
+    #[test]
+    fn smooth_below_has_correct_values() {
+        assert_eq!(smooth_below(3, 0), []);
+        assert_eq!(smooth_below(3, 1), []);
+        assert_eq!(smooth_below(3, 2), [1]);
+        assert_eq!(smooth_below(3, 20), [1, 2, 3, 4, 6, 8, 9, 12, 16, 18]);
+    }
+
+    #[test]
+    fn smooth_below_does_not_overflow_past_u64() {
+        // 2^64 - 1 is far beyond the point where `smooth` panics.
+        assert_eq!(
+            smooth_below(3, u64::MAX).last(),
+            Some(&17991041643939889152)
+        );
+    }
+
+    #[test]
+    fn count_smooth_below_matches_length() {
+        for k in [0, 1, 2, 3, 5, 7] {
+            assert_eq!(count_smooth_below(k, 1000), smooth_below(k, 1000).len());
+        }
+    }
+
+    #[test]
+    fn smooth_factored_matches_smooth() {
+        for (k, n) in [(0, 100), (1, 100), (2, 64), (3, 100), (5, 100), (7, 100)] {
+            let factored = smooth_factored(k, n);
+            let values: Vec<u64> = factored.iter().map(|&(v, _)| v).collect();
+            assert_eq!(values, smooth(k, n));
+        }
+    }
+
+    #[test]
+    fn smooth_factored_exponents_reconstruct_the_value() {
+        let primes = [2, 3, 5];
+        for &(value, ref exponents) in &with_primes_factored(&primes, 200) {
+            assert_eq!(exponents.len(), primes.len());
+            let reconstructed: u64 = primes
+                .iter()
+                .zip(exponents)
+                .map(|(&p, &e)| p.pow(e))
+                .product();
+            assert_eq!(reconstructed, value);
+        }
+    }
+
+    #[test]
+    fn smooth_factored_known_value() {
+        let factored = smooth_factored(5, 100);
+        assert_eq!(factored[0], (1, vec![0, 0, 0]));
+        let (value, exponents) = factored.iter().find(|&&(v, _)| v == 720).unwrap();
+        assert_eq!((*value, exponents.clone()), (720, vec![4, 2, 1]));
+    }
+
+    #[test]
+    fn with_primes_factored_single_prime() {
+        assert_eq!(
+            with_primes_factored(&[2], 5),
+            [
+                (1, vec![0]),
+                (2, vec![1]),
+                (4, vec![2]),
+                (8, vec![3]),
+                (16, vec![4]),
+            ]
+        );
+    }
 }